@@ -5,6 +5,7 @@ mod seat;
 mod session_lock;
 mod window;
 
+use crate::macros::static_assert_size;
 use crate::{time::Instant, window::Id};
 use sctk::reexports::client::protocol::{
     wl_output::WlOutput, wl_seat::WlSeat, wl_surface::WlSurface,
@@ -37,3 +38,7 @@ pub enum Event {
     /// Request Resize
     RequestResize,
 }
+
+// Pins the enum's current layout; bump this to the size reported by the
+// compiler's diagnostic if a deliberate change grows it.
+static_assert_size!(Event, 56);
@@ -1,20 +1,85 @@
 //! Widget and Window IDs.
 
-use std::borrow;
+use crate::macros::static_assert_size;
+
+use std::collections::HashMap;
 use std::num::NonZeroU128;
 use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Mutex, OnceLock};
 
 static NEXT_ID: AtomicU64 = AtomicU64::new(1);
 static NEXT_WINDOW_ID: AtomicU64 = AtomicU64::new(1);
 
+/// An interned string, modeled on rustc's `Symbol`.
+///
+/// Interning guarantees that equal strings always produce the same
+/// [`Symbol`], so comparing or hashing a [`Symbol`] only ever touches a
+/// single `u32` instead of the underlying string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Interns `string` in the global symbol table, returning a [`Symbol`]
+    /// that will always compare equal for equal inputs.
+    pub fn intern(string: &str) -> Self {
+        interner().lock().expect("interner lock poisoned").intern(string)
+    }
+
+    /// Resolves this [`Symbol`] back into the string it was interned from.
+    pub fn resolve(self) -> &'static str {
+        interner().lock().expect("interner lock poisoned").resolve(self)
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.resolve())
+    }
+}
+
+#[derive(Default)]
+struct Interner {
+    strings: Vec<&'static str>,
+    names: HashMap<&'static str, u32>,
+}
+
+impl Interner {
+    fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(&id) = self.names.get(string) {
+            return Symbol(id);
+        }
+
+        let id = self.strings.len() as u32;
+        // Leaked once per distinct string for the lifetime of the program,
+        // which is what lets `resolve` hand back a `&'static str`.
+        let string: &'static str = Box::leak(string.to_owned().into_boxed_str());
+
+        self.strings.push(string);
+        self.names.insert(string, id);
+
+        Symbol(id)
+    }
+
+    fn resolve(&self, symbol: Symbol) -> &'static str {
+        self.strings[symbol.0 as usize]
+    }
+}
+
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(|| Mutex::new(Interner::default()))
+}
+
 /// The identifier of a generic widget.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Id(pub Internal);
 
+static_assert_size!(Id, 24);
+
 impl Id {
     /// Creates a custom [`Id`].
-    pub fn new(id: impl Into<borrow::Cow<'static, str>>) -> Self {
-        Self(Internal::Custom(Self::next(), id.into()))
+    pub fn new(id: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+        Self(Internal::Custom(Self::next(), Symbol::intern(&id.into())))
     }
 
     /// resets the id counter
@@ -34,6 +99,29 @@ impl Id {
 
         Self(Internal::Unique(id))
     }
+
+    /// Creates a unique [`Id`], recording `label` as a human-readable name
+    /// for it.
+    ///
+    /// The label is only used to resolve a meaningful [`Display`](std::fmt::Display)
+    /// representation (e.g. for debugging or accessibility trees); it plays
+    /// no part in equality or hashing, which are still based purely on the
+    /// underlying unique number.
+    pub fn unique_labeled(label: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+        let id = Self::next();
+
+        labels()
+            .lock()
+            .expect("labels lock poisoned")
+            .insert(id, Symbol::intern(&label.into()));
+
+        Self(Internal::Unique(id))
+    }
+}
+
+fn labels() -> &'static Mutex<HashMap<u64, Symbol>> {
+    static LABELS: OnceLock<Mutex<HashMap<u64, Symbol>>> = OnceLock::new();
+    LABELS.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
 // Not meant to be used directly
@@ -43,58 +131,121 @@ impl From<u64> for Id {
     }
 }
 
+/// The error produced when an [`Id`] has no corresponding accessibility
+/// node id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdConversionError {
+    /// an [`Internal::Set`] does not itself name a single accessibility
+    /// node; only its members do
+    Set,
+}
+
+impl std::fmt::Display for IdConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Set => {
+                write!(f, "cannot convert a set id to a NonZeroU128")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IdConversionError {}
+
+/// The namespace an accessibility node id belongs to.
+///
+/// Every node id handed to the accessibility tree is tagged with one of
+/// these, reserving the upper bits of the [`NonZeroU128`] so that
+/// counters in different namespaces can never collide with one another,
+/// regardless of how each counter is incremented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u128)]
+enum Namespace {
+    /// a widget id, produced from [`Internal::Unique`] or [`Internal::Custom`]
+    Widget = 1,
+    /// a window id, produced by [`window_node_id`]
+    Window = 2,
+}
+
+/// Packs `index` into the low bits of a [`NonZeroU128`] tagged with
+/// `namespace` in the high bits, so that ids from different namespaces
+/// are always distinct.
+fn tagged(namespace: Namespace, index: u64) -> NonZeroU128 {
+    NonZeroU128::new(((namespace as u128) << 64) | index as u128)
+        .expect("namespace tag is never zero")
+}
+
 // Not meant to be used directly
-impl From<Id> for NonZeroU128 {
-    fn from(id: Id) -> NonZeroU128 {
+impl TryFrom<Id> for NonZeroU128 {
+    type Error = IdConversionError;
+
+    fn try_from(id: Id) -> Result<Self, Self::Error> {
         match &id.0 {
-            Internal::Unique(id) => NonZeroU128::try_from(*id as u128).unwrap(),
-            Internal::Custom(id, _) => {
-                NonZeroU128::try_from(*id as u128).unwrap()
-            }
-            // this is a set id, which is not a valid id and will not ever be converted to a NonZeroU128
-            // so we panic
-            Internal::Set(_) => {
-                panic!("Cannot convert a set id to a NonZeroU128")
+            Internal::Unique(id) | Internal::Custom(id, _) => {
+                Ok(tagged(Namespace::Widget, *id))
             }
+            // a set id does not name a single accessibility node, so it
+            // has no valid conversion
+            Internal::Set(_) => Err(IdConversionError::Set),
         }
     }
 }
 
 impl std::fmt::Display for Id {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.0 {
-            Internal::Unique(_) => write!(f, "Undefined"),
-            Internal::Custom(_, id) => write!(f, "{}", id.to_string()),
-            Internal::Set(_) => write!(f, "Set"),
-        }
+        self.0.fmt(f)
     }
 }
 
-// XXX WIndow IDs are made unique by adding u64::MAX to them
 /// get window node id that won't conflict with other node ids for the duration of the program
 pub fn window_node_id() -> NonZeroU128 {
-    std::num::NonZeroU128::try_from(
-        u64::MAX as u128
-            + NEXT_WINDOW_ID.fetch_add(1, atomic::Ordering::Relaxed) as u128,
+    tagged(
+        Namespace::Window,
+        NEXT_WINDOW_ID.fetch_add(1, atomic::Ordering::Relaxed),
     )
-    .unwrap()
 }
 
-// TODO refactor to make panic impossible?
 #[derive(Debug, Clone, Eq)]
 /// Internal representation of an [`Id`].
 pub enum Internal {
     /// a unique id
     Unique(u64),
     /// a custom id, which is equal to any [`Id`] with a matching number or string
-    Custom(u64, borrow::Cow<'static, str>),
-    /// XXX Do not use this as an id for an accessibility node, it will panic!
+    Custom(u64, Symbol),
+    /// XXX Do not use this as an id for an accessibility node, conversion will fail!
     /// XXX Only meant to be used for widgets that have multiple accessibility nodes, each with a
     /// unique or custom id
     /// an Id Set, which is equal to any [`Id`] with a matching number or string
     Set(Vec<Self>),
 }
 
+static_assert_size!(Internal, 24);
+
+impl std::fmt::Display for Internal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unique(id) => match labels().lock().expect("labels lock poisoned").get(id) {
+                Some(label) => write!(f, "{}", label),
+                None => write!(f, "{}", id),
+            },
+            Self::Custom(_, id) => write!(f, "{}", id),
+            Self::Set(ids) => {
+                write!(f, "Set[")?;
+
+                for (i, id) in ids.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "{}", id)?;
+                }
+
+                write!(f, "]")
+            }
+        }
+    }
+}
+
 impl PartialEq for Internal {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -152,4 +303,43 @@ mod tests {
 
         assert_ne!(a, b);
     }
+
+    #[test]
+    fn interning_identical_strings_yields_the_same_symbol() {
+        let a = super::Symbol::intern("foo");
+        let b = super::Symbol::intern("foo");
+        let c = super::Symbol::intern("bar");
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn labeled_unique_ids_display_their_label() {
+        let id = Id::unique_labeled("ScrollThumb");
+
+        assert_eq!(id.to_string(), "ScrollThumb");
+    }
+
+    #[test]
+    fn window_and_widget_node_ids_never_collide() {
+        use std::num::NonZeroU128;
+
+        let widget: NonZeroU128 = Id::unique().try_into().unwrap();
+        let window = super::window_node_id();
+
+        assert_ne!(widget, window);
+    }
+
+    #[test]
+    fn set_ids_cannot_be_converted_to_node_ids() {
+        use std::num::NonZeroU128;
+
+        let set = Id(super::Internal::Set(vec![Id::unique().0]));
+
+        assert_eq!(
+            NonZeroU128::try_from(set),
+            Err(super::IdConversionError::Set)
+        );
+    }
 }
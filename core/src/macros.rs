@@ -0,0 +1,16 @@
+//! Compile-time layout guards.
+
+/// Asserts that `$ty` is exactly `$size` bytes, modeled on rustc's
+/// `static_assert_size!`.
+///
+/// This catches accidental layout regressions (e.g. a new enum variant
+/// silently growing a hot-path type) at compile time: a mismatch fails
+/// with a "mismatched types" diagnostic naming the expected and found
+/// array lengths, which are the expected and actual byte sizes.
+macro_rules! static_assert_size {
+    ($ty:ty, $size:expr) => {
+        const _: [(); $size] = [(); ::std::mem::size_of::<$ty>()];
+    };
+}
+
+pub(crate) use static_assert_size;